@@ -0,0 +1,8 @@
+pub mod coin_selection;
+pub mod create_transaction_error;
+pub mod create_transactions;
+pub mod ledger_signer;
+pub mod multisig;
+pub mod psbt;
+pub mod signer;
+pub mod sighash_type;
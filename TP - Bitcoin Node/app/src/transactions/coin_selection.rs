@@ -0,0 +1,186 @@
+use bitcoin::block_mod::tx_out::TxOut;
+
+use super::create_transaction_error::TransactionCreateError;
+
+/// Estimated virtual size, in bytes, of a single P2PKH input (prevout +
+/// scriptSig + signature + pubkey).
+pub(crate) const P2PKH_INPUT_VSIZE: i64 = 148;
+/// Estimated virtual size, in bytes, of a single P2WPKH input (the
+/// signature/pubkey move to the discounted witness).
+pub(crate) const P2WPKH_INPUT_VSIZE: i64 = 68;
+/// Estimated virtual size, in bytes, of the change output a selection adds
+/// when it can't hit the target exactly.
+const CHANGE_OUTPUT_VSIZE: i64 = 34;
+/// Bounds how long the branch-and-bound search runs before giving up and
+/// falling back to accumulative selection.
+const BNB_MAX_ITERATIONS: usize = 100_000;
+
+pub(crate) struct CoinSelection<T> {
+    pub selected: Vec<(Vec<u8>, u32, TxOut, T)>,
+    /// Whether the caller still needs to add a change output, i.e. whether
+    /// Branch-and-Bound failed to hit the target exactly.
+    pub needs_change: bool,
+}
+
+/// Selects UTXOs to cover `target`, preferring Bitcoin Core's
+/// Branch-and-Bound algorithm (no change output) and falling back to
+/// accumulative (knapsack-style) selection when no exact match exists.
+///
+/// `input_vsize` is the estimated virtual size of a single input of the
+/// kind being spent, used with `fee_rate` (sat/vbyte) to compute each
+/// UTXO's effective value: what it actually contributes once the cost of
+/// including it is subtracted.
+pub(crate) fn select_coins<T>(
+    mut utxo: Vec<(Vec<u8>, u32, TxOut, T)>,
+    target: i64,
+    fee_rate: i64,
+    input_vsize: i64,
+) -> Result<CoinSelection<T>, TransactionCreateError> {
+    let cost_of_change = CHANGE_OUTPUT_VSIZE * fee_rate;
+    let effective_value: Vec<i64> = utxo
+        .iter()
+        .map(|(_, _, txout, _)| txout.get_value() - input_vsize * fee_rate)
+        .collect();
+
+    let mut order: Vec<usize> = (0..utxo.len()).collect();
+    order.sort_unstable_by(|&a, &b| effective_value[b].cmp(&effective_value[a]));
+
+    if let Some(indices) = branch_and_bound(&effective_value, &order, target, cost_of_change) {
+        return Ok(CoinSelection {
+            selected: take_indices(&mut utxo, &indices),
+            needs_change: false,
+        });
+    }
+
+    accumulative_select(&mut utxo, &order, target)
+}
+
+/// Depth-first search over include/exclude decisions for each UTXO (ordered
+/// by descending effective value), trying to land the selected value in
+/// `[target, target + cost_of_change]` without a change output. The
+/// inclusion branch is explored first, since it converges on a match faster
+/// when big UTXOs are available.
+fn branch_and_bound(effective_value: &[i64], order: &[usize], target: i64, cost_of_change: i64) -> Option<Vec<usize>> {
+    let upper_bound = target + cost_of_change;
+    let total_available: i64 = order.iter().map(|&i| effective_value[i]).sum();
+
+    let mut iterations = 0usize;
+    let mut selected = vec![];
+
+    fn recurse(
+        effective_value: &[i64],
+        order: &[usize],
+        depth: usize,
+        selected: &mut Vec<usize>,
+        selected_value: i64,
+        remaining_available: i64,
+        target: i64,
+        upper_bound: i64,
+        iterations: &mut usize,
+    ) -> bool {
+        *iterations += 1;
+        if *iterations > BNB_MAX_ITERATIONS {
+            return false;
+        }
+        if selected_value > upper_bound {
+            return false; // overshoot: prune
+        }
+        if selected_value >= target {
+            return true; // landed inside the window: exact match
+        }
+        if depth == order.len() || selected_value + remaining_available < target {
+            return false; // can't reach the target from here: prune
+        }
+
+        let value = effective_value[order[depth]];
+
+        selected.push(order[depth]);
+        if recurse(effective_value, order, depth + 1, selected, selected_value + value, remaining_available - value, target, upper_bound, iterations) {
+            return true;
+        }
+        selected.pop();
+
+        recurse(effective_value, order, depth + 1, selected, selected_value, remaining_available - value, target, upper_bound, iterations)
+    }
+
+    if recurse(effective_value, order, 0, &mut selected, 0, total_available, target, upper_bound, &mut iterations) {
+        Some(selected)
+    } else {
+        None
+    }
+}
+
+/// Falls back to accumulating UTXOs (largest effective value first) until
+/// the target is covered, accepting that the excess becomes a change output.
+fn accumulative_select<T>(utxo: &mut Vec<(Vec<u8>, u32, TxOut, T)>, order: &[usize], target: i64) -> Result<CoinSelection<T>, TransactionCreateError> {
+    let mut acum_amount = 0;
+    let mut indices = vec![];
+
+    for &index in order {
+        if acum_amount >= target {
+            break;
+        }
+        acum_amount += utxo[index].2.get_value();
+        indices.push(index);
+    }
+
+    if acum_amount < target {
+        return Err(TransactionCreateError::InsufficientFounds);
+    }
+
+    Ok(CoinSelection {
+        selected: take_indices(utxo, &indices),
+        needs_change: true,
+    })
+}
+
+/// Removes and returns the UTXOs at `indices` from `utxo`, preserving the
+/// other entries. Indices are consumed highest-first so earlier removals
+/// never shift the ones still to come.
+fn take_indices<T>(utxo: &mut Vec<(Vec<u8>, u32, TxOut, T)>, indices: &[usize]) -> Vec<(Vec<u8>, u32, TxOut, T)> {
+    let mut sorted = indices.to_vec();
+    sorted.sort_unstable_by(|a, b| b.cmp(a));
+
+    sorted.into_iter().map(|index| utxo.remove(index)).collect()
+}
+
+#[cfg(test)]
+mod coin_selection_test {
+    use super::*;
+
+    fn utxo(value: i64) -> (Vec<u8>, u32, TxOut, ()) {
+        (vec![0u8; 32], 0, TxOut::new(value, vec![]), ())
+    }
+
+    #[test]
+    fn test_branch_and_bound_picks_exact_match_with_no_change() {
+        let utxo = vec![utxo(1_000), utxo(2_000)];
+
+        let selection = select_coins(utxo, 1_000, 0, 0).unwrap();
+
+        assert!(!selection.needs_change);
+        assert_eq!(selection.selected.len(), 1);
+        assert_eq!(selection.selected[0].2.get_value(), 1_000);
+    }
+
+    #[test]
+    fn test_falls_back_to_accumulative_when_no_exact_match() {
+        let utxo = vec![utxo(300), utxo(300)];
+
+        let selection = select_coins(utxo, 500, 0, 0).unwrap();
+
+        assert!(selection.needs_change);
+        assert_eq!(selection.selected.len(), 2);
+        let total: i64 = selection.selected.iter().map(|(_, _, txout, _)| txout.get_value()).sum();
+        assert_eq!(total, 600);
+    }
+
+    #[test]
+    fn test_insufficient_funds() {
+        let utxo = vec![utxo(100)];
+
+        let result = select_coins(utxo, 500, 0, 0);
+
+        assert!(matches!(result, Err(TransactionCreateError::InsufficientFounds)));
+    }
+}
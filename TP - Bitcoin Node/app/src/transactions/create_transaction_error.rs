@@ -0,0 +1,42 @@
+use std::fmt;
+
+/// Errors raised while building, signing or finalizing a transaction.
+#[derive(Debug)]
+pub enum TransactionCreateError {
+    /// A raw private key slice was not a valid secp256k1 scalar.
+    PrivateKey,
+    /// The supplied UTXOs do not cover the target amount plus fee.
+    InsufficientFounds,
+    /// A PSBT was malformed: missing magic bytes, a truncated key-value pair,
+    /// invalid base64, or a missing or undecodable unsigned transaction.
+    /// Unknown key types are skipped, not rejected.
+    InvalidPsbt,
+    /// `finalize_psbt` was called on an input that is still missing a
+    /// signature for every public key its script requires, or, for a
+    /// multisig input, has fewer signatures than its redeem/witness
+    /// script's threshold; `sign_transaction` also returns it when a
+    /// multisig input has fewer available cosigners than its threshold.
+    MissingSignatures,
+    /// A multisig redeem script was requested with a threshold of zero,
+    /// a threshold above the number of public keys, or more than the 16
+    /// public keys `OP_CHECKMULTISIG` can verify.
+    InvalidMultisigThreshold,
+    /// A Ledger APDU exchange failed, or the device's response didn't
+    /// contain the field being parsed out of it.
+    LedgerCommunication,
+}
+
+impl fmt::Display for TransactionCreateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransactionCreateError::PrivateKey => write!(f, "invalid private key"),
+            TransactionCreateError::InsufficientFounds => write!(f, "insufficient funds"),
+            TransactionCreateError::InvalidPsbt => write!(f, "malformed PSBT"),
+            TransactionCreateError::MissingSignatures => write!(f, "PSBT input is missing required signatures"),
+            TransactionCreateError::InvalidMultisigThreshold => write!(f, "invalid multisig threshold"),
+            TransactionCreateError::LedgerCommunication => write!(f, "failed to communicate with the Ledger device"),
+        }
+    }
+}
+
+impl std::error::Error for TransactionCreateError {}
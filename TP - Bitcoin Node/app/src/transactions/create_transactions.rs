@@ -1,19 +1,39 @@
 use bitcoin::block_mod::{script::Script, transaction::Transaction, tx_out::TxOut, tx_in::TxIn};
-use bitcoin_hashes::{hash160, sha256, Hash, sha256d};
-use secp256k1::{SecretKey, Secp256k1, PublicKey, Message};
+use bitcoin_hashes::{hash160, Hash, sha256d};
 use bech32::wit_prog::WitnessProgram;
 
+use super::coin_selection::{select_coins, P2PKH_INPUT_VSIZE, P2WPKH_INPUT_VSIZE};
 use super::create_transaction_error::TransactionCreateError;
+use super::multisig;
+use super::sighash_type::{SigHashBase, SigHashType};
+use super::signer::{SigningRequest, TxSigner};
 
 
 pub fn is_string_bech32(address: String) -> bool{
     WitnessProgram::from_address("tb".to_string(), address).is_ok()
 }
 
-fn is_array_bech32(address: &[u8]) -> bool{
+pub(crate) fn is_array_bech32(address: &[u8]) -> bool{
     is_string_bech32(String::from_utf8_lossy(address).to_string())
 }
 
+/// Version byte a testnet P2PKH address (`m.../n...`) is encoded behind.
+pub(crate) const P2PKH_VERSION_PREFIX: u8 = 0x6f;
+/// Version byte a testnet P2SH address (`2...`) is encoded behind.
+pub(crate) const P2SH_VERSION_PREFIX: u8 = 0xc4;
+
+/// Base58Check-encodes `payload` (a hash160) behind `version`, appending
+/// the standard double-SHA256 checksum.
+pub(crate) fn base58check_encode(version: u8, payload: &[u8]) -> Vec<u8> {
+    let version_prefix: [u8; 1] = [version];
+    let double_hash = sha256d::Hash::hash(&[&version_prefix[..], payload].concat());
+    let checksum = &double_hash[..4];
+
+    let input = [&version_prefix[..], payload, checksum].concat();
+
+    bs58::encode(input).into_vec()
+}
+
 fn address_from_pubkey(public_key: &[u8], p2wpkh: bool) -> Vec<u8>{
     let h160 = hash160::Hash::hash(public_key).to_byte_array();
 
@@ -22,17 +42,11 @@ fn address_from_pubkey(public_key: &[u8], p2wpkh: bool) -> Vec<u8>{
             version: 0,
             program: h160.to_vec(),
         };
-    
+
         return witness_program.to_address("tb".to_string()).unwrap().as_bytes().to_vec();
     }
 
-    let version_prefix: [u8; 1] = [0x6f];
-    let double_hash = sha256d::Hash::hash(&[&version_prefix[..], &h160[..]].concat());    
-    let checksum = &double_hash[..4];
-    
-    let input = [&version_prefix[..], &h160[..], checksum].concat();
-
-    bs58::encode(input).into_vec()
+    base58check_encode(P2PKH_VERSION_PREFIX, &h160)
 }
 
 fn decode_base58(address: &Vec<u8>) -> Vec<u8> {
@@ -43,6 +57,15 @@ fn decode_base58(address: &Vec<u8>) -> Vec<u8> {
     Vec::new()
 }
 
+/// Whether `address` is a base58check-encoded testnet P2SH address, i.e. it
+/// decodes to the [`P2SH_VERSION_PREFIX`] version byte.
+fn is_array_p2sh(address: &[u8]) -> bool {
+    match bs58::decode(address).into_vec() {
+        Ok(combined) => combined.first() == Some(&P2SH_VERSION_PREFIX),
+        Err(_) => false,
+    }
+}
+
 pub fn pk_script_from_pubkey(public_key: &[u8], p2wpkh: bool) -> Vec<u8> {
     let address = address_from_pubkey(public_key, p2wpkh);
 
@@ -51,7 +74,7 @@ pub fn pk_script_from_pubkey(public_key: &[u8], p2wpkh: bool) -> Vec<u8> {
 
 pub fn pk_script_from_address(address: &Vec<u8>, p2wpkh: bool) -> Vec<u8>{
     if p2wpkh{
-        let string_address = String::from_utf8_lossy(address).to_string(); 
+        let string_address = String::from_utf8_lossy(address).to_string();
 
         if let Ok(witness_program) = WitnessProgram::from_address("tb".to_string(), string_address){
             return witness_program.to_scriptpubkey();
@@ -59,11 +82,17 @@ pub fn pk_script_from_address(address: &Vec<u8>, p2wpkh: bool) -> Vec<u8>{
     }
 
     let h160 = decode_base58(address);
+
+    if is_array_p2sh(address) {
+        let script = Script::new(Some(vec![vec![0xa9], h160, vec![0x87]]));
+        return script.as_bytes();
+    }
+
     let script = Script::new(Some(vec![vec![0x76], vec![0xa9], h160, vec![0x88], vec![0xac]]));
     script.as_bytes()
 }
 
-fn create_txout_list(targets: Vec<(Vec<u8>, i64)>, fee: i64) -> (Vec<TxOut>, i64){
+pub(crate) fn create_txout_list(targets: Vec<(Vec<u8>, i64)>, fee: i64) -> (Vec<TxOut>, i64){
     let mut total_amount = fee;
     let mut txout_list = vec![];
 
@@ -77,77 +106,275 @@ fn create_txout_list(targets: Vec<(Vec<u8>, i64)>, fee: i64) -> (Vec<TxOut>, i64
     (txout_list, total_amount)
 }
 
-fn create_txin_list(mut utxo: Vec<(Vec<u8>, u32, TxOut)>, total_amount: i64) -> Result<(Vec<TxIn>, Vec<i64>), TransactionCreateError> {
+/// A boxed [`TxSigner`] paired with the BIP32 derivation path it should sign
+/// with for one specific input — an in-memory key ignoring an empty path
+/// for `LocalSigner`, or a shared `LedgerSigner` handle and that input's own
+/// path when the key lives on a hardware wallet.
+pub type SignerHandle = (Box<dyn TxSigner>, Vec<u32>);
+
+/// What unlocks one [`OwnedUtxo`]: a single key signed the way `p2wpkh`
+/// (passed to `sign_transaction`/`create_transaction`) says, or an `m`-of-`n`
+/// multisig input signed by whichever cosigners are available locally.
+/// `public_keys` always lists every cosigner's key, in the order the
+/// multisig redeem/witness script commits to, so the script can be rebuilt
+/// even when `signers` holds fewer than `n` of them.
+pub enum SpendAuth {
+    Single(SignerHandle),
+    /// A P2SH multisig input: signatures land in the final scriptSig
+    /// alongside the redeem script.
+    P2shMultisig { threshold: u8, public_keys: Vec<Vec<u8>>, signers: Vec<SignerHandle> },
+    /// A P2WSH multisig input: the same script, signed the same way, but the
+    /// signatures and witness script land on the witness stack instead.
+    P2wshMultisig { threshold: u8, public_keys: Vec<Vec<u8>>, signers: Vec<SignerHandle> },
+}
+
+/// A UTXO together with the [`SpendAuth`] that unlocks it, so a wallet
+/// spreading funds across several HD addresses — or keeping keys off the
+/// host entirely, or spending a shared multisig output — can still build one
+/// transaction spending from all of them.
+pub type OwnedUtxo = (Vec<u8>, u32, TxOut, SpendAuth);
+
+/// Selects UTXOs to cover `total_amount` via [`select_coins`], carrying along
+/// whatever payload `T` each one was tagged with (a [`SpendAuth`] to spend it
+/// immediately, an [`InputScript`](super::psbt::InputScript) for a
+/// watch-only PSBT, `()` if nothing is needed). The returned `bool` says
+/// whether a change output is still needed: Branch-and-Bound selection that
+/// lands on the target exactly needs none.
+pub(crate) fn create_txin_list<T>(utxo: Vec<(Vec<u8>, u32, TxOut, T)>, total_amount: i64, fee_rate: i64, p2wpkh: bool) -> Result<(Vec<TxIn>, Vec<i64>, Vec<T>, bool), TransactionCreateError> {
+    let input_vsize = if p2wpkh { P2WPKH_INPUT_VSIZE } else { P2PKH_INPUT_VSIZE };
+    let selection = select_coins(utxo, total_amount, fee_rate, input_vsize)?;
+
     let mut txin_list = vec![];
     let mut amount_list = vec![];
+    let mut payload_list = vec![];
     let mut acum_amount = 0;
 
-    while acum_amount < total_amount {
-        if let Some(txout) = utxo.pop() {
-            let txin = TxIn::new(txout.0, txout.1, vec![], 0xffffffff);
-
-            acum_amount += txout.2.get_value();
+    for (prev_tx, index, txout, payload) in selection.selected {
+        txin_list.push(TxIn::new(prev_tx, index, vec![], 0xffffffff));
 
-            txin_list.push(txin);
-            amount_list.push(txout.2.get_value());
-        } else {
-            return Err(TransactionCreateError::InsufficientFounds);
-        }
+        acum_amount += txout.get_value();
+        amount_list.push(txout.get_value());
+        payload_list.push(payload);
     }
 
-    amount_list.push(acum_amount - total_amount);   //Change difference that must return to the sender
+    if selection.needs_change {
+        amount_list.push(acum_amount - total_amount);   //Change difference that must return to the sender
+    }
 
-    Ok((txin_list, amount_list))
+    Ok((txin_list, amount_list, payload_list, selection.needs_change))
 }
 
 
 
-fn sign_transaction(transaction: &mut Transaction, private_key: SecretKey, pk_script: &[u8], p2wpkh: bool, amount_list: &[i64]){
-    let secp = Secp256k1::new();
-    let mut signature_hash;
+/// The sighash `SIGHASH_SINGLE` is defined to produce when there is no
+/// output at the same index as the input being signed — the historical
+/// "SIGHASH_SINGLE bug" that every implementation must reproduce verbatim
+/// rather than erroring out, since it's now part of consensus-adjacent
+/// signing behaviour. Equal to the 256-bit integer `1`, serialized the way
+/// a signature hash is.
+pub(crate) const SIGHASH_SINGLE_BUG_HASH: [u8; 32] = {
+    let mut hash = [0u8; 32];
+    hash[0] = 1;
+    hash
+};
+
+/// Whether signing input `index` of a transaction with `tx_out_count`
+/// outputs under `sighash_type` hits the `SIGHASH_SINGLE` bug, in which case
+/// the caller must use [`SIGHASH_SINGLE_BUG_HASH`] instead of calling
+/// [`transaction_for_sighash`] at all.
+pub(crate) fn is_sighash_single_bug(tx_out_count: usize, index: usize, sighash_type: SigHashType) -> bool {
+    sighash_type.base == SigHashBase::Single && index >= tx_out_count
+}
 
-    for i in 0..transaction.get_tx_in_list().len(){
-        if p2wpkh{
-            signature_hash = transaction.p2wpkh_signature_hash(i, pk_script.to_vec(), amount_list.to_vec());
-        } else{
-            signature_hash = transaction.p2pkh_signature_hash(i, pk_script);
+/// Builds the transaction a given input's signature hash must be computed
+/// over: for `None` drops every output, for `Single` keeps outputs `0..=index`
+/// but zeroes out (value `-1`, empty script) every one of them before
+/// `index` and drops everything after, for `None`/`Single` without
+/// `AnyoneCanPay` also zeroes every other input's `nSequence` (per the
+/// legacy/BIP143 sighash rules, since those two base types let other inputs'
+/// sequence numbers change after this signature is made), and for
+/// `AnyoneCanPay` keeps only the input being signed. Returns the restricted
+/// transaction along with that input's new index within it.
+///
+/// Must not be called when [`is_sighash_single_bug`] is true for this input
+/// — there is no output left to keep, so the caller has to substitute
+/// [`SIGHASH_SINGLE_BUG_HASH`] instead.
+pub(crate) fn transaction_for_sighash(transaction: &Transaction, index: usize, sighash_type: SigHashType) -> (Transaction, usize) {
+    let mut tx_in_list = transaction.get_tx_in_list().clone();
+    let mut tx_out_list = transaction.get_tx_out_list().clone();
+    let mut working_index = index;
+
+    match sighash_type.base {
+        SigHashBase::None => tx_out_list = vec![],
+        SigHashBase::Single => {
+            tx_out_list.truncate(index + 1);
+            for txout in tx_out_list.iter_mut().take(index) {
+                *txout = TxOut::new(-1, vec![]);
+            }
         }
-        
-        let message = Message::from_hashed_data::<sha256::Hash>(&signature_hash);        
-        let mut signature = secp.sign_ecdsa(&message, &private_key).serialize_der().to_vec();
-        signature.push(0x01);
-
-        let pubkey = PublicKey::from_secret_key(&secp, &private_key).serialize().to_vec();
-        let script = vec![signature, pubkey];
-
-        if p2wpkh{
-            transaction.set_witness(script);
-        } else{
-            let signature_script = Script::new(Some(script));    
-            transaction.set_signature(i, signature_script.as_bytes());
+        SigHashBase::All => {}
+    }
+
+    if !sighash_type.anyone_can_pay && sighash_type.base != SigHashBase::All {
+        for (i, tx_in) in tx_in_list.iter_mut().enumerate() {
+            if i != index {
+                tx_in.set_sequence(0);
+            }
         }
     }
+
+    if sighash_type.anyone_can_pay {
+        tx_in_list = vec![tx_in_list[index].clone()];
+        working_index = 0;
+    }
+
+    (Transaction::new(1, tx_in_list, tx_out_list, 0), working_index)
 }
 
+/// The signature hash input `index` commits to: [`SIGHASH_SINGLE_BUG_HASH`]
+/// when [`is_sighash_single_bug`] applies, otherwise the legacy or BIP143
+/// hash of [`transaction_for_sighash`]'s restricted transaction over
+/// `pk_script` — the scriptCode a single key's own template, or a multisig
+/// redeem/witness script, is signed over.
+fn signature_hash_for_input(transaction: &Transaction, index: usize, pk_script: &[u8], is_witness: bool, amount_list: &[i64], sighash_type: SigHashType) -> Vec<u8> {
+    if is_sighash_single_bug(transaction.get_tx_out_list().len(), index, sighash_type) {
+        return SIGHASH_SINGLE_BUG_HASH.to_vec();
+    }
 
-pub fn create_transaction(targets: Vec<(Vec<u8>, i64)>, utxo: Vec<(Vec<u8>, u32, TxOut)>, private_key: &[u8], fee: i64, p2wpkh: bool) -> Result<Transaction, TransactionCreateError> {
-    let secp = Secp256k1::new();
+    let (working_tx, working_index) = transaction_for_sighash(transaction, index, sighash_type);
+    let working_amounts = if sighash_type.anyone_can_pay {
+        vec![amount_list[index]]
+    } else {
+        amount_list.to_vec()
+    };
 
-    let private_key = SecretKey::from_slice(private_key).map_err(|_| TransactionCreateError::PrivateKey)?;
-    let public_key = PublicKey::from_secret_key(&secp, &private_key).serialize().to_vec();
-    let pk_script = pk_script_from_pubkey(&public_key, p2wpkh);
+    if is_witness {
+        working_tx.p2wpkh_signature_hash(working_index, pk_script.to_vec(), working_amounts)
+    } else {
+        working_tx.p2pkh_signature_hash(working_index, pk_script)
+    }
+}
 
+/// Signs input `index`, owned by a single key, and splices the signature
+/// into `transaction`'s scriptSig or witness.
+fn sign_single_input(transaction: &mut Transaction, index: usize, signer: &mut SignerHandle, p2wpkh: bool, amount_list: &[i64], sighash_type: SigHashType) -> Result<(), TransactionCreateError> {
+    let (signer, derivation_path) = signer;
+    let pubkey = signer.public_key(derivation_path)?;
+    let pk_script = pk_script_from_pubkey(&pubkey, p2wpkh);
+
+    let signature_hash = signature_hash_for_input(transaction, index, &pk_script, p2wpkh, amount_list, sighash_type);
+
+    let request = SigningRequest {
+        sighash: &signature_hash,
+        transaction: &*transaction,
+        input_index: index,
+        input_amounts: amount_list,
+        pk_script: &pk_script,
+        sighash_type,
+    };
+    let mut signature = signer.sign_input(&request, derivation_path)?;
+    signature.push(sighash_type.to_byte());
+
+    let script = vec![signature, pubkey];
+
+    if p2wpkh {
+        transaction.set_witness(script);
+    } else {
+        let signature_script = Script::new(Some(script));
+        transaction.set_signature(index, signature_script.as_bytes());
+    }
+
+    Ok(())
+}
+
+/// Signs input `index`, an `m`-of-`n` multisig owned by `signers` (a subset
+/// of `public_keys`'s cosigners), and splices the result into `transaction`'s
+/// scriptSig (P2SH) or witness (P2WSH): every available cosigner signs over
+/// the redeem/witness script itself, [`multisig::ordered_multisig_signatures`]
+/// puts the ones collected back in the script's own key order, and
+/// [`multisig::multisig_signature_script`]/[`multisig::multisig_witness_stack`]
+/// build the final spend alongside it.
+fn sign_multisig_input(
+    transaction: &mut Transaction,
+    index: usize,
+    threshold: u8,
+    public_keys: &[Vec<u8>],
+    signers: &mut [SignerHandle],
+    is_witness: bool,
+    amount_list: &[i64],
+    sighash_types: &[SigHashType],
+) -> Result<(), TransactionCreateError> {
+    let redeem_script = multisig::redeem_script(threshold, public_keys)?;
+    let sighash_type = sighash_types.get(index).copied().unwrap_or_default();
+
+    let mut signatures = Vec::with_capacity(signers.len());
+    for (signer, derivation_path) in signers.iter_mut() {
+        let pubkey = signer.public_key(derivation_path)?;
+        let signature_hash = signature_hash_for_input(transaction, index, &redeem_script, is_witness, amount_list, sighash_type);
+
+        let request = SigningRequest {
+            sighash: &signature_hash,
+            transaction: &*transaction,
+            input_index: index,
+            input_amounts: amount_list,
+            pk_script: &redeem_script,
+            sighash_type,
+        };
+        let mut signature = signer.sign_input(&request, derivation_path)?;
+        signature.push(sighash_type.to_byte());
+
+        signatures.push((pubkey, signature));
+    }
+
+    let ordered_signatures = multisig::ordered_multisig_signatures(public_keys, &signatures, threshold)?;
+
+    if is_witness {
+        transaction.set_witness(multisig::multisig_witness_stack(&ordered_signatures, &redeem_script));
+    } else {
+        transaction.set_signature(index, multisig::multisig_signature_script(&ordered_signatures, &redeem_script));
+    }
+
+    Ok(())
+}
+
+/// Signs every input in place via its [`SpendAuth`] — a single in-memory or
+/// hardware-backed [`SignerHandle`], or several cosigners' `SignerHandle`s
+/// for a P2SH/P2WSH multisig input — none of which `sign_transaction` itself
+/// needs further details about.
+pub(crate) fn sign_transaction(transaction: &mut Transaction, inputs: &mut [SpendAuth], p2wpkh: bool, amount_list: &[i64], sighash_types: &[SigHashType]) -> Result<(), TransactionCreateError> {
+    for (i, input) in inputs.iter_mut().enumerate() {
+        let sighash_type = sighash_types.get(i).copied().unwrap_or_default();
+
+        match input {
+            SpendAuth::Single(signer) => sign_single_input(transaction, i, signer, p2wpkh, amount_list, sighash_type)?,
+            SpendAuth::P2shMultisig { threshold, public_keys, signers } => {
+                sign_multisig_input(transaction, i, *threshold, public_keys, signers, false, amount_list, sighash_types)?
+            }
+            SpendAuth::P2wshMultisig { threshold, public_keys, signers } => {
+                sign_multisig_input(transaction, i, *threshold, public_keys, signers, true, amount_list, sighash_types)?
+            }
+        }
+    }
+
+    Ok(())
+}
+
+
+pub fn create_transaction(targets: Vec<(Vec<u8>, i64)>, utxo: Vec<OwnedUtxo>, change_address: &[u8], fee: i64, fee_rate: i64, p2wpkh: bool, sighash_types: &[SigHashType]) -> Result<Transaction, TransactionCreateError> {
     let (mut txout_list, total_amount) = create_txout_list(targets, fee);
-    let (txin_list, amount_list)= create_txin_list(utxo, total_amount)?;
+    let (txin_list, amount_list, mut signer_list, needs_change) = create_txin_list(utxo, total_amount, fee_rate, p2wpkh)?;
 
-    if let Some(change) = amount_list.last(){
-        let txout_change = TxOut::new(*change, pk_script.clone());
-        txout_list.push(txout_change);
+    if needs_change {
+        if let Some(change) = amount_list.last(){
+            let change_script = pk_script_from_address(&change_address.to_vec(), p2wpkh);
+            let txout_change = TxOut::new(*change, change_script);
+            txout_list.push(txout_change);
+        }
     }
 
     let mut transaction = Transaction::new(1, txin_list, txout_list, 0);
 
-    sign_transaction(&mut transaction, private_key, &pk_script, p2wpkh, &amount_list);
+    sign_transaction(&mut transaction, &mut signer_list, p2wpkh, &amount_list, sighash_types)?;
 
     Ok(transaction)
 }
@@ -160,9 +387,9 @@ mod create_transactions_test {
     use bitcoin_hashes::*;
     use secp256k1::{Secp256k1, Message, SecretKey, PublicKey};
 
-    use crate::transactions::{create_transactions::{decode_base58, is_string_bech32, address_from_pubkey, is_array_bech32}, create_transaction_error::TransactionCreateError};
+    use crate::transactions::{create_transactions::{decode_base58, is_string_bech32, address_from_pubkey, is_array_bech32}, create_transaction_error::TransactionCreateError, sighash_type::SigHashType};
 
-    use super::{pk_script_from_address};
+    use super::{pk_script_from_address, is_sighash_single_bug, transaction_for_sighash, SIGHASH_SINGLE_BUG_HASH};
 
     #[test]
     pub fn create_transaction() -> Result<(), TransactionCreateError>{
@@ -363,4 +590,75 @@ mod create_transactions_test {
 
         Ok(())
     }
+
+    #[test]
+    pub fn test_sighash_single_truncates_outputs() {
+        let tx_in_list = vec![
+            TxIn::new(vec![0u8; 32], 0, vec![], 0xffffffff),
+            TxIn::new(vec![1u8; 32], 1, vec![], 0xffffffff),
+        ];
+        let tx_out_list = vec![
+            TxOut::new(1_000, vec![0x51]),
+            TxOut::new(2_000, vec![0x52]),
+            TxOut::new(3_000, vec![0x53]),
+        ];
+        let transaction = Transaction::new(1, tx_in_list, tx_out_list, 0);
+
+        let (working_tx, working_index) = transaction_for_sighash(&transaction, 1, SigHashType::SINGLE);
+
+        assert_eq!(working_index, 1);
+        let restricted = working_tx.get_tx_out_list();
+        assert_eq!(restricted.len(), 2);
+        assert_eq!(restricted[0].get_value(), -1);
+        assert!(restricted[0].get_pk_script().is_empty());
+        assert_eq!(restricted[1].get_value(), 2_000);
+        assert_eq!(restricted[1].get_pk_script(), &[0x52]);
+    }
+
+    #[test]
+    pub fn test_sighash_single_zeros_other_input_sequences() {
+        let tx_in_list = vec![
+            TxIn::new(vec![0u8; 32], 0, vec![], 0xffffffff),
+            TxIn::new(vec![1u8; 32], 1, vec![], 0xfffffffe),
+        ];
+        let tx_out_list = vec![TxOut::new(1_000, vec![0x51]), TxOut::new(2_000, vec![0x52])];
+        let transaction = Transaction::new(1, tx_in_list, tx_out_list, 0);
+
+        let (working_tx, _) = transaction_for_sighash(&transaction, 0, SigHashType::SINGLE);
+        let restricted = working_tx.get_tx_in_list();
+
+        assert_eq!(restricted[0].get_sequence(), 0xffffffff);
+        assert_eq!(restricted[1].get_sequence(), 0);
+    }
+
+    #[test]
+    pub fn test_sighash_all_leaves_sequences_untouched() {
+        let tx_in_list = vec![
+            TxIn::new(vec![0u8; 32], 0, vec![], 0xffffffff),
+            TxIn::new(vec![1u8; 32], 1, vec![], 0xfffffffe),
+        ];
+        let tx_out_list = vec![TxOut::new(1_000, vec![0x51])];
+        let transaction = Transaction::new(1, tx_in_list, tx_out_list, 0);
+
+        let (working_tx, _) = transaction_for_sighash(&transaction, 0, SigHashType::ALL);
+        let restricted = working_tx.get_tx_in_list();
+
+        assert_eq!(restricted[0].get_sequence(), 0xffffffff);
+        assert_eq!(restricted[1].get_sequence(), 0xfffffffe);
+    }
+
+    #[test]
+    pub fn test_sighash_single_bug_when_no_matching_output() {
+        let tx_in_list = vec![
+            TxIn::new(vec![0u8; 32], 0, vec![], 0xffffffff),
+            TxIn::new(vec![1u8; 32], 1, vec![], 0xffffffff),
+        ];
+        let tx_out_list = vec![TxOut::new(1_000, vec![0x51])];
+        let transaction = Transaction::new(1, tx_in_list, tx_out_list, 0);
+
+        assert!(!is_sighash_single_bug(transaction.get_tx_out_list().len(), 0, SigHashType::SINGLE));
+        assert!(is_sighash_single_bug(transaction.get_tx_out_list().len(), 1, SigHashType::SINGLE));
+        assert_eq!(SIGHASH_SINGLE_BUG_HASH[0], 1);
+        assert!(SIGHASH_SINGLE_BUG_HASH[1..].iter().all(|&b| b == 0));
+    }
 }
\ No newline at end of file
@@ -0,0 +1,153 @@
+use bitcoin::messages::compact_size::CompactSizeUInt;
+use ledger_apdu::APDUCommand;
+use ledger_transport_hid::{hidapi::HidApi, TransportNativeHID};
+
+use super::create_transaction_error::TransactionCreateError;
+use super::signer::{SigningRequest, TxSigner};
+
+/// CLA byte Ledger's Bitcoin app expects on every APDU.
+const CLA_BTC: u8 = 0xe0;
+/// INS: return the public key for a BIP32 derivation path.
+const INS_GET_PUBLIC_KEY: u8 = 0x40;
+/// INS: start (or add an input to) the transaction hash the device is
+/// accumulating.
+const INS_HASH_INPUT_START: u8 = 0x44;
+/// INS: finish streaming the transaction's outputs into that same hash.
+const INS_HASH_INPUT_FINALIZE_FULL: u8 = 0x4a;
+/// INS: sign the input most recently passed to [`INS_HASH_INPUT_START`]
+/// with the key at a BIP32 path.
+const INS_HASH_SIGN: u8 = 0x48;
+
+/// `p1` for [`INS_HASH_INPUT_START`]: this begins a new transaction hash.
+const P1_NEW_TRANSACTION: u8 = 0x00;
+/// `p1` for [`INS_HASH_INPUT_START`]: this adds another input to a hash
+/// already begun by [`P1_NEW_TRANSACTION`].
+const P1_CONTINUE_TRANSACTION: u8 = 0x80;
+/// `p2` for [`INS_HASH_INPUT_START`]/[`INS_HASH_INPUT_FINALIZE_FULL`]: every
+/// input's outpoint and amount are provided directly (rather than via a
+/// `GET TRUSTED INPUT` round trip per prevout) so the device can verify
+/// amounts for segwit inputs without needing each prevout's full raw
+/// transaction — the only prevout data `OwnedUtxo`/`Psbt` keep around.
+const P2_OUTPOINT_AND_AMOUNT: u8 = 0x02;
+/// Marker byte preceding each input in [`INS_HASH_INPUT_START`] once
+/// [`P2_OUTPOINT_AND_AMOUNT`] is in effect.
+const INPUT_MARKER_OUTPOINT_AND_AMOUNT: u8 = 0x02;
+/// `p1` for [`INS_HASH_INPUT_FINALIZE_FULL`]: this is the last (here, only)
+/// chunk of the output list.
+const P1_FINALIZE_LAST_CHUNK: u8 = 0x00;
+
+/// Talks to a Ledger device's Bitcoin app over USB HID so the private key
+/// never leaves the device: streams the transaction's inputs and outputs
+/// via `HASH INPUT START`/`HASH INPUT FINALIZE FULL` so the device derives
+/// and displays its own hash, then exchanges the BIP32 derivation path and
+/// sighash type for a DER signature over the input most recently streamed —
+/// rather than trusting a sighash computed on the host, which is all a
+/// compromised machine would need to get the device to blindly sign.
+pub struct LedgerSigner {
+    transport: TransportNativeHID,
+}
+
+impl LedgerSigner {
+    /// Opens the first Ledger device found over USB HID.
+    pub fn connect() -> Result<LedgerSigner, TransactionCreateError> {
+        let hidapi = HidApi::new().map_err(|_| TransactionCreateError::LedgerCommunication)?;
+        let transport = TransportNativeHID::new(&hidapi).map_err(|_| TransactionCreateError::LedgerCommunication)?;
+
+        Ok(LedgerSigner { transport })
+    }
+
+    fn exchange(&self, ins: u8, p1: u8, p2: u8, data: Vec<u8>) -> Result<Vec<u8>, TransactionCreateError> {
+        let command = APDUCommand { cla: CLA_BTC, ins, p1, p2, data };
+
+        self.transport
+            .exchange(&command)
+            .map(|answer| answer.data().to_vec())
+            .map_err(|_| TransactionCreateError::LedgerCommunication)
+    }
+
+    /// `HASH INPUT START` for the whole transaction: declares its version
+    /// and input count, then streams every input's outpoint and amount in
+    /// its own APDU, substituting `request.pk_script` for the scriptSig of
+    /// the input actually being signed and leaving every other input's
+    /// script empty, per the Bitcoin app's hashing protocol.
+    fn hash_input_start(&self, request: &SigningRequest) -> Result<(), TransactionCreateError> {
+        let tx_in_list = request.transaction.get_tx_in_list();
+
+        let mut header = request.transaction.get_version().to_le_bytes().to_vec();
+        header.extend_from_slice(&CompactSizeUInt::from_number(tx_in_list.len() as u64).as_bytes());
+        self.exchange(INS_HASH_INPUT_START, P1_NEW_TRANSACTION, P2_OUTPOINT_AND_AMOUNT, header)?;
+
+        for (index, tx_in) in tx_in_list.iter().enumerate() {
+            let script = if index == request.input_index { request.pk_script } else { &[] };
+
+            let mut data = vec![INPUT_MARKER_OUTPOINT_AND_AMOUNT];
+            data.extend_from_slice(tx_in.get_prev_tx());
+            data.extend_from_slice(&tx_in.get_index().to_le_bytes());
+            data.extend_from_slice(&request.input_amounts.get(index).copied().unwrap_or(0).to_le_bytes());
+            data.extend_from_slice(&CompactSizeUInt::from_number(script.len() as u64).as_bytes());
+            data.extend_from_slice(script);
+            data.extend_from_slice(&tx_in.get_sequence().to_le_bytes());
+
+            self.exchange(INS_HASH_INPUT_START, P1_CONTINUE_TRANSACTION, P2_OUTPOINT_AND_AMOUNT, data)?;
+        }
+
+        Ok(())
+    }
+
+    /// `HASH INPUT FINALIZE FULL`: streams every output so the device can
+    /// display them before signing, completing the hash `hash_input_start`
+    /// began.
+    fn hash_input_finalize_full(&self, request: &SigningRequest) -> Result<(), TransactionCreateError> {
+        let tx_out_list = request.transaction.get_tx_out_list();
+
+        let mut data = CompactSizeUInt::from_number(tx_out_list.len() as u64).as_bytes();
+        for tx_out in tx_out_list {
+            data.extend_from_slice(&tx_out.get_value().to_le_bytes());
+            data.extend_from_slice(&CompactSizeUInt::from_number(tx_out.get_pk_script().len() as u64).as_bytes());
+            data.extend_from_slice(tx_out.get_pk_script());
+        }
+
+        self.exchange(INS_HASH_INPUT_FINALIZE_FULL, P1_FINALIZE_LAST_CHUNK, 0x00, data)?;
+
+        Ok(())
+    }
+
+    /// `HASH SIGN`: signs the input most recently streamed with the key at
+    /// `derivation_path`, committing to the sighash type and the
+    /// transaction's locktime the same way the hash preimage would.
+    fn hash_sign(&self, request: &SigningRequest, derivation_path: &[u32]) -> Result<Vec<u8>, TransactionCreateError> {
+        let mut data = encode_derivation_path(derivation_path);
+        data.push(0x00); // user validation code length: none
+        data.push(request.sighash_type.to_byte());
+        data.extend_from_slice(&request.transaction.get_lock_time().to_le_bytes());
+
+        self.exchange(INS_HASH_SIGN, 0x00, 0x00, data)
+    }
+}
+
+impl TxSigner for LedgerSigner {
+    fn sign_input(&mut self, request: &SigningRequest, derivation_path: &[u32]) -> Result<Vec<u8>, TransactionCreateError> {
+        self.hash_input_start(request)?;
+        self.hash_input_finalize_full(request)?;
+        self.hash_sign(request, derivation_path)
+    }
+
+    fn public_key(&mut self, derivation_path: &[u32]) -> Result<Vec<u8>, TransactionCreateError> {
+        let response = self.exchange(INS_GET_PUBLIC_KEY, 0x00, 0x00, encode_derivation_path(derivation_path))?;
+
+        let len = *response.first().ok_or(TransactionCreateError::LedgerCommunication)? as usize;
+        response.get(1..1 + len).map(<[u8]>::to_vec).ok_or(TransactionCreateError::LedgerCommunication)
+    }
+}
+
+/// Encodes a BIP32 derivation path the way every Ledger APDU expects it: a
+/// one-byte depth followed by each index as big-endian `u32`.
+fn encode_derivation_path(derivation_path: &[u32]) -> Vec<u8> {
+    let mut encoded = vec![derivation_path.len() as u8];
+
+    for index in derivation_path {
+        encoded.extend_from_slice(&index.to_be_bytes());
+    }
+
+    encoded
+}
@@ -0,0 +1,262 @@
+use bitcoin::block_mod::script::Script;
+use bitcoin_hashes::{hash160, sha256, Hash};
+use bech32::wit_prog::WitnessProgram;
+
+use super::create_transaction_error::TransactionCreateError;
+use super::create_transactions::{base58check_encode, P2SH_VERSION_PREFIX};
+
+/// `OP_CHECKMULTISIG`.
+const OP_CHECKMULTISIG: u8 = 0xae;
+/// Highest `m`/`n` `OP_CHECKMULTISIG` can verify without `OP_CHECKMULTISIGVERIFY`-style tricks.
+const MAX_PUBLIC_KEYS: usize = 16;
+
+/// Builds the `m`-of-`n` script `OP_m <pubkey>... OP_n OP_CHECKMULTISIG` that
+/// backs a P2SH redeem script or a P2WSH witness script — the two contexts
+/// share this exact format and differ only in how the script is hashed into
+/// an address. `public_keys` are kept in the order given, since that order
+/// is part of what the script commits to.
+pub fn redeem_script(threshold: u8, public_keys: &[Vec<u8>]) -> Result<Vec<u8>, TransactionCreateError> {
+    let n = public_keys.len();
+
+    if threshold == 0 || n == 0 || usize::from(threshold) > n || n > MAX_PUBLIC_KEYS {
+        return Err(TransactionCreateError::InvalidMultisigThreshold);
+    }
+
+    let mut parts = vec![vec![0x50 + threshold]];
+    parts.extend(public_keys.iter().cloned());
+    parts.push(vec![0x50 + n as u8]);
+    parts.push(vec![OP_CHECKMULTISIG]);
+
+    Ok(Script::new(Some(parts)).as_bytes())
+}
+
+/// Parses an `OP_m <pubkey>... OP_n OP_CHECKMULTISIG` script back into the
+/// threshold and public keys [`redeem_script`] built it from — the pubkey
+/// order a multisig input's signatures must line up with, so a PSBT
+/// finalizer can recover it from the `redeem_script`/`witness_script` it
+/// already carries instead of having to track it separately.
+pub fn parse_multisig_script(script: &[u8]) -> Option<(u8, Vec<Vec<u8>>)> {
+    if script.len() < 3 || *script.last()? != OP_CHECKMULTISIG {
+        return None;
+    }
+
+    let threshold = script.first()?.checked_sub(0x50)?;
+    let n = script.get(script.len() - 2)?.checked_sub(0x50)?;
+
+    if threshold == 0 || n == 0 || threshold > n {
+        return None;
+    }
+
+    let mut public_keys = vec![];
+    let mut pos = 1;
+    let end = script.len() - 2;
+
+    while pos < end {
+        let len = *script.get(pos)? as usize;
+        pos += 1;
+        public_keys.push(script.get(pos..pos + len)?.to_vec());
+        pos += len;
+    }
+
+    if pos != end || public_keys.len() != usize::from(n) {
+        return None;
+    }
+
+    Some((threshold, public_keys))
+}
+
+/// Hashes a redeem script into the P2SH scriptPubKey that pays into it:
+/// `OP_HASH160 <hash160(redeem_script)> OP_EQUAL`.
+pub fn pk_script_from_redeem_script(redeem_script: &[u8]) -> Vec<u8> {
+    let h160 = hash160::Hash::hash(redeem_script).to_byte_array();
+
+    Script::new(Some(vec![vec![0xa9], h160.to_vec(), vec![0x87]])).as_bytes()
+}
+
+/// Base58Check-encodes a redeem script's hash160 behind the testnet P2SH
+/// version byte, the way [`pk_script_from_address`](super::create_transactions::pk_script_from_address)
+/// recognizes it for spending.
+pub fn address_from_redeem_script(redeem_script: &[u8]) -> Vec<u8> {
+    let h160 = hash160::Hash::hash(redeem_script).to_byte_array();
+
+    base58check_encode(P2SH_VERSION_PREFIX, &h160)
+}
+
+/// Builds the scriptSig that spends a P2SH multisig output: the empty
+/// element `OP_CHECKMULTISIG`'s off-by-one bug consumes, the signatures in
+/// redeem-script key order, and the redeem script itself so the network can
+/// verify it hashes to the scriptPubKey.
+pub fn multisig_signature_script(signatures: &[Vec<u8>], redeem_script: &[u8]) -> Vec<u8> {
+    let mut parts = vec![vec![]];
+    parts.extend(signatures.iter().cloned());
+    parts.push(redeem_script.to_vec());
+
+    Script::new(Some(parts)).as_bytes()
+}
+
+fn v0_witness_program(witness_script: &[u8]) -> WitnessProgram {
+    WitnessProgram {
+        version: 0,
+        program: sha256::Hash::hash(witness_script).to_byte_array().to_vec(),
+    }
+}
+
+/// Hashes a witness script into the P2WSH scriptPubKey that pays into it: a
+/// v0 witness program over its sha256 (32 bytes, unlike P2WPKH's 20-byte
+/// hash160 of a single pubkey).
+pub fn pk_script_from_witness_script(witness_script: &[u8]) -> Vec<u8> {
+    v0_witness_program(witness_script).to_scriptpubkey()
+}
+
+/// Bech32-encodes a witness script's sha256 behind a v0 witness program,
+/// the testnet P2WSH address spending it resolves to.
+pub fn address_from_witness_script(witness_script: &[u8]) -> Vec<u8> {
+    v0_witness_program(witness_script)
+        .to_address("tb".to_string())
+        .unwrap()
+        .as_bytes()
+        .to_vec()
+}
+
+/// Builds the witness stack that spends a P2WSH multisig output: the empty
+/// element `OP_CHECKMULTISIG`'s off-by-one bug consumes, the signatures in
+/// witness-script key order, and the witness script itself so the network
+/// can verify it hashes to the scriptPubKey. Unlike [`multisig_signature_script`],
+/// these items go straight onto the witness stack rather than through a
+/// `Script`, since nothing here is executed as scriptSig opcodes.
+pub fn multisig_witness_stack(signatures: &[Vec<u8>], witness_script: &[u8]) -> Vec<Vec<u8>> {
+    let mut stack = vec![vec![]];
+    stack.extend(signatures.iter().cloned());
+    stack.push(witness_script.to_vec());
+
+    stack
+}
+
+/// Picks `public_keys`' own order's first `threshold` signatures out of
+/// `signatures` — the order `OP_CHECKMULTISIG` requires them in — erroring
+/// if fewer than `threshold` cosigners have signed yet. `signatures` pairs
+/// each signer's pubkey with the signature it produced, in no particular
+/// order, the way partially-signed cosigners accumulate whether they arrive
+/// through a PSBT's `partial_sigs` or a direct multisig spend.
+pub fn ordered_multisig_signatures(
+    public_keys: &[Vec<u8>],
+    signatures: &[(Vec<u8>, Vec<u8>)],
+    threshold: u8,
+) -> Result<Vec<Vec<u8>>, TransactionCreateError> {
+    let mut ordered: Vec<Vec<u8>> = public_keys
+        .iter()
+        .filter_map(|pubkey| signatures.iter().find(|(key, _)| key == pubkey))
+        .map(|(_, signature)| signature.clone())
+        .collect();
+
+    if ordered.len() < usize::from(threshold) {
+        return Err(TransactionCreateError::MissingSignatures);
+    }
+    ordered.truncate(threshold.into());
+
+    Ok(ordered)
+}
+
+#[cfg(test)]
+mod multisig_test {
+    use bitcoin_hashes::{hash160, sha256, Hash};
+
+    use super::*;
+
+    fn compressed_pubkey(tag: u8) -> Vec<u8> {
+        let mut pubkey = vec![0x02];
+        pubkey.extend(std::iter::repeat(tag).take(32));
+        pubkey
+    }
+
+    #[test]
+    fn test_redeem_script_2_of_3() {
+        let pubkeys = vec![compressed_pubkey(1), compressed_pubkey(2), compressed_pubkey(3)];
+
+        let script = redeem_script(2, &pubkeys).unwrap();
+
+        let mut expected = vec![0x52]; // OP_2
+        for pubkey in &pubkeys {
+            expected.push(0x21); // push 33 bytes
+            expected.extend_from_slice(pubkey);
+        }
+        expected.push(0x53); // OP_3
+        expected.push(OP_CHECKMULTISIG);
+
+        assert_eq!(script, expected);
+    }
+
+    #[test]
+    fn test_parse_multisig_script_recovers_threshold_and_pubkeys() {
+        let pubkeys = vec![compressed_pubkey(1), compressed_pubkey(2), compressed_pubkey(3)];
+        let script = redeem_script(2, &pubkeys).unwrap();
+
+        assert_eq!(parse_multisig_script(&script), Some((2, pubkeys)));
+    }
+
+    #[test]
+    fn test_parse_multisig_script_rejects_non_multisig_scripts() {
+        assert_eq!(parse_multisig_script(&[0x76, 0xa9, 0x88, 0xac]), None);
+    }
+
+    #[test]
+    fn test_redeem_script_rejects_invalid_thresholds() {
+        let pubkeys = vec![compressed_pubkey(1), compressed_pubkey(2), compressed_pubkey(3)];
+
+        assert!(matches!(redeem_script(0, &pubkeys), Err(TransactionCreateError::InvalidMultisigThreshold)));
+        assert!(matches!(redeem_script(4, &pubkeys), Err(TransactionCreateError::InvalidMultisigThreshold)));
+        assert!(matches!(redeem_script(1, &[]), Err(TransactionCreateError::InvalidMultisigThreshold)));
+    }
+
+    #[test]
+    fn test_p2sh_multisig_address_and_pk_script_agree_on_hash160() {
+        let pubkeys = vec![compressed_pubkey(1), compressed_pubkey(2), compressed_pubkey(3)];
+        let script = redeem_script(2, &pubkeys).unwrap();
+        let expected_hash = hash160::Hash::hash(&script).to_byte_array();
+
+        let pk_script = pk_script_from_redeem_script(&script);
+        assert_eq!(pk_script, [&[0xa9, 0x14][..], &expected_hash[..], &[0x87][..]].concat());
+
+        let address = address_from_redeem_script(&script);
+        let decoded = bs58::decode(&address).into_vec().unwrap();
+        assert_eq!(decoded[0], P2SH_VERSION_PREFIX);
+        assert_eq!(decoded[1..decoded.len() - 4], expected_hash);
+    }
+
+    #[test]
+    fn test_p2wsh_multisig_pk_script_matches_witness_program() {
+        let pubkeys = vec![compressed_pubkey(1), compressed_pubkey(2), compressed_pubkey(3)];
+        let script = redeem_script(2, &pubkeys).unwrap();
+        let expected_hash = sha256::Hash::hash(&script).to_byte_array();
+
+        let pk_script = pk_script_from_witness_script(&script);
+        assert_eq!(pk_script, [&[0x00, 0x20][..], &expected_hash[..]].concat());
+    }
+
+    #[test]
+    fn test_multisig_signature_script_and_witness_stack_carry_every_signature() {
+        let pubkeys = vec![compressed_pubkey(1), compressed_pubkey(2), compressed_pubkey(3)];
+        let script = redeem_script(2, &pubkeys).unwrap();
+        let signatures = vec![vec![0xaa; 71], vec![0xbb; 72]];
+
+        let witness_stack = multisig_witness_stack(&signatures, &script);
+        assert_eq!(witness_stack, vec![vec![], signatures[0].clone(), signatures[1].clone(), script.clone()]);
+
+        let signature_script = multisig_signature_script(&signatures, &script);
+        assert!(!signature_script.is_empty());
+    }
+
+    #[test]
+    fn test_ordered_multisig_signatures_follows_public_key_order_and_enforces_threshold() {
+        let pubkeys = vec![compressed_pubkey(1), compressed_pubkey(2), compressed_pubkey(3)];
+        let signatures = vec![(pubkeys[2].clone(), vec![0xcc]), (pubkeys[0].clone(), vec![0xaa])];
+
+        let ordered = ordered_multisig_signatures(&pubkeys, &signatures, 2).unwrap();
+        assert_eq!(ordered, vec![vec![0xaa], vec![0xcc]]);
+
+        assert!(matches!(
+            ordered_multisig_signatures(&pubkeys, &signatures[..1], 2),
+            Err(TransactionCreateError::MissingSignatures)
+        ));
+    }
+}
@@ -0,0 +1,459 @@
+use bitcoin::block_mod::{transaction::Transaction, tx_out::TxOut, script::Script};
+use secp256k1::{Message, Secp256k1, SecretKey, PublicKey};
+use bitcoin_hashes::{sha256, Hash};
+
+use super::create_transaction_error::TransactionCreateError;
+use super::create_transactions::{create_txin_list, create_txout_list, is_array_bech32, is_sighash_single_bug, pk_script_from_address, pk_script_from_pubkey, transaction_for_sighash, SIGHASH_SINGLE_BUG_HASH};
+use super::multisig;
+use super::sighash_type::SigHashType;
+
+/// Which script backs a PSBT input being created, so [`create_psbt`] knows
+/// whether to fill in `witness_utxo` and how to build `redeem_script`/
+/// `witness_script` for a multisig spend, without carrying any key or
+/// signer the way `create_transaction`'s `SpendAuth` does.
+#[derive(Clone)]
+pub enum InputScript {
+    P2pkh,
+    P2wpkh,
+    /// A P2SH multisig input, backed by the `m`-of-`n` `redeem_script`
+    /// [`multisig::redeem_script`] builds from `threshold` and `public_keys`.
+    P2shMultisig { threshold: u8, public_keys: Vec<Vec<u8>> },
+    /// A P2WSH multisig input — the same script, carried as `witness_script`
+    /// and spent via the witness rather than the scriptSig.
+    P2wshMultisig { threshold: u8, public_keys: Vec<Vec<u8>> },
+}
+
+impl InputScript {
+    fn is_witness(&self) -> bool {
+        matches!(self, InputScript::P2wpkh | InputScript::P2wshMultisig { .. })
+    }
+}
+
+/// BIP174 magic bytes every serialized PSBT starts with.
+const PSBT_MAGIC: [u8; 5] = [0x70, 0x73, 0x62, 0x74, 0xff];
+
+/// Per-input PSBT data, following the BIP174 input map.
+#[derive(Clone, Default)]
+pub struct PsbtInput {
+    /// Set only for a witness input: `create_psbt` only ever has a previous
+    /// output's `TxOut` to work from, not its whole raw transaction, so a
+    /// non-witness input has no BIP174 `non_witness_utxo` proof to carry —
+    /// that field isn't supported here.
+    pub witness_utxo: Option<TxOut>,
+    pub sighash_type: Option<u8>,
+    /// `(pubkey, signature)` pairs collected from every signer so far.
+    pub partial_sigs: Vec<(Vec<u8>, Vec<u8>)>,
+    pub redeem_script: Option<Vec<u8>>,
+    pub witness_script: Option<Vec<u8>>,
+    pub final_script_sig: Option<Vec<u8>>,
+    pub final_script_witness: Option<Vec<Vec<u8>>>,
+    pub p2wpkh: bool,
+}
+
+/// Per-output PSBT data, following the BIP174 output map.
+#[derive(Clone, Default)]
+pub struct PsbtOutput {
+    pub redeem_script: Option<Vec<u8>>,
+    pub witness_script: Option<Vec<u8>>,
+}
+
+/// A partially-signed Bitcoin transaction (BIP174): an unsigned transaction
+/// plus, for every input and output, the extra data a signer or finalizer
+/// needs without having to be handed the original `(targets, utxo)` lists.
+#[derive(Clone)]
+pub struct Psbt {
+    pub unsigned_tx: Transaction,
+    pub inputs: Vec<PsbtInput>,
+    pub outputs: Vec<PsbtOutput>,
+}
+
+/// Creator + Updater: builds an unsigned PSBT from the same `(targets, utxo)`
+/// shape `create_transaction` takes, filling in `sighash_type` for every
+/// input and `witness_utxo` for every witness input so a signer can work
+/// off the PSBT alone,
+/// and — just like `create_transaction` — appending a change output paying
+/// `change_address` whenever coin selection doesn't land on the target
+/// exactly.
+pub fn create_psbt(
+    targets: Vec<(Vec<u8>, i64)>,
+    utxo: Vec<(Vec<u8>, u32, TxOut, InputScript)>,
+    change_address: &[u8],
+    fee: i64,
+    fee_rate: i64,
+    sighash_type: SigHashType,
+) -> Result<Psbt, TransactionCreateError> {
+    let (mut txout_list, total_amount) = create_txout_list(targets, fee);
+
+    let taggable_utxo: Vec<(Vec<u8>, u32, TxOut, (Vec<u8>, u32, TxOut, InputScript))> = utxo
+        .into_iter()
+        .map(|(prev_tx, index, txout, script)| {
+            (prev_tx.clone(), index, txout.clone(), (prev_tx, index, txout, script))
+        })
+        .collect();
+    // A watch-only PSBT may mix P2PKH, P2WPKH and multisig inputs, so a
+    // single conservative P2PKH-sized estimate is used for every candidate here.
+    let (txin_list, amount_list, selected, needs_change) = create_txin_list(taggable_utxo, total_amount, fee_rate, false)?;
+
+    if needs_change {
+        if let Some(change) = amount_list.last() {
+            let change_script = pk_script_from_address(&change_address.to_vec(), is_array_bech32(change_address));
+            txout_list.push(TxOut::new(*change, change_script));
+        }
+    }
+
+    let mut inputs = Vec::with_capacity(selected.len());
+    for (_, _, txout, script) in &selected {
+        let is_witness = script.is_witness();
+
+        let mut input = PsbtInput {
+            witness_utxo: if is_witness { Some(txout.clone()) } else { None },
+            sighash_type: Some(sighash_type.to_byte()),
+            partial_sigs: vec![],
+            redeem_script: None,
+            witness_script: None,
+            final_script_sig: None,
+            final_script_witness: None,
+            p2wpkh: is_witness,
+        };
+
+        match script {
+            InputScript::P2pkh | InputScript::P2wpkh => {}
+            InputScript::P2shMultisig { threshold, public_keys } => {
+                input.redeem_script = Some(multisig::redeem_script(*threshold, public_keys)?);
+            }
+            InputScript::P2wshMultisig { threshold, public_keys } => {
+                input.witness_script = Some(multisig::redeem_script(*threshold, public_keys)?);
+            }
+        }
+
+        inputs.push(input);
+    }
+
+    let outputs = txout_list.iter().map(|_| PsbtOutput::default()).collect();
+
+    Ok(Psbt {
+        unsigned_tx: Transaction::new(1, txin_list, txout_list, 0),
+        inputs,
+        outputs,
+    })
+}
+
+/// The script a signer must hash `sighash_type` over for one input: the
+/// witness script for a P2WSH spend (including P2WSH multisig — the actual
+/// scriptCode BIP143 commits to, which can't be recovered from its
+/// `witness_utxo` scriptPubKey alone since that only carries the script's
+/// hash), the plain scriptPubKey for a P2WPKH input (the crate's BIP143
+/// hasher derives the legacy-style scriptCode from it itself), the redeem
+/// script for a non-witness multisig spend, or a single key's own P2PKH
+/// template otherwise.
+fn pk_script_for_input(input: &PsbtInput, public_key: &[u8]) -> Vec<u8> {
+    if let Some(witness_script) = &input.witness_script {
+        return witness_script.clone();
+    }
+    if let Some(witness_utxo) = &input.witness_utxo {
+        return witness_utxo.get_pk_script().to_vec();
+    }
+    if let Some(redeem_script) = &input.redeem_script {
+        return redeem_script.clone();
+    }
+    pk_script_from_pubkey(public_key, input.p2wpkh)
+}
+
+/// Signer: adds this key's signature to every input of the PSBT, keyed off
+/// the PSBT's own `witness_utxo` and `sighash_type` rather than a
+/// separately-passed amount/script list. It does not check whether the key
+/// is one that an input's script actually requires.
+pub fn sign_psbt(psbt: &mut Psbt, private_key: &SecretKey) -> Result<(), TransactionCreateError> {
+    let secp = Secp256k1::new();
+    let public_key = PublicKey::from_secret_key(&secp, private_key).serialize().to_vec();
+
+    let amount_list: Vec<i64> = psbt
+        .inputs
+        .iter()
+        .map(|input| input.witness_utxo.as_ref().map(|txout| txout.get_value()).unwrap_or(0))
+        .collect();
+
+    for (i, input) in psbt.inputs.iter_mut().enumerate() {
+        let pk_script = pk_script_for_input(input, &public_key);
+        let sighash_type = SigHashType::from_byte(input.sighash_type.unwrap_or(0x01));
+
+        let signature_hash = if is_sighash_single_bug(psbt.unsigned_tx.get_tx_out_list().len(), i, sighash_type) {
+            SIGHASH_SINGLE_BUG_HASH.to_vec()
+        } else {
+            let (working_tx, working_index) = transaction_for_sighash(&psbt.unsigned_tx, i, sighash_type);
+            let working_amounts = if sighash_type.anyone_can_pay {
+                vec![amount_list[i]]
+            } else {
+                amount_list.clone()
+            };
+
+            if input.p2wpkh {
+                working_tx.p2wpkh_signature_hash(working_index, pk_script.clone(), working_amounts)
+            } else {
+                working_tx.p2pkh_signature_hash(working_index, &pk_script)
+            }
+        };
+
+        let message = Message::from_hashed_data::<sha256::Hash>(&signature_hash);
+        let mut signature = secp.sign_ecdsa(&message, private_key).serialize_der().to_vec();
+        signature.push(sighash_type.to_byte());
+
+        input.partial_sigs.push((public_key.clone(), signature));
+    }
+
+    Ok(())
+}
+
+/// Finalizer: moves each input's `partial_sigs` into `final_scriptSig`
+/// (P2PKH, P2SH multisig) or `final_scriptWitness` (P2WPKH, P2WSH multisig)
+/// and extracts the network-serializable `Transaction`. A multisig input is
+/// recognised straight off its `redeem_script`/`witness_script` via
+/// [`multisig::parse_multisig_script`], so this works for any PSBT carrying
+/// one — not just one built through [`create_psbt`].
+pub fn finalize_psbt(mut psbt: Psbt) -> Result<Transaction, TransactionCreateError> {
+    for input in psbt.inputs.iter_mut() {
+        let multisig_script = input.witness_script.as_ref().or(input.redeem_script.as_ref()).cloned();
+        let multisig_script = multisig_script.and_then(|script| multisig::parse_multisig_script(&script).map(|parsed| (script, parsed)));
+
+        if let Some((script, (threshold, public_keys))) = multisig_script {
+            let ordered_signatures = multisig::ordered_multisig_signatures(&public_keys, &input.partial_sigs, threshold)?;
+
+            if input.witness_script.is_some() {
+                input.final_script_witness = Some(multisig::multisig_witness_stack(&ordered_signatures, &script));
+            } else {
+                input.final_script_sig = Some(multisig::multisig_signature_script(&ordered_signatures, &script));
+            }
+            continue;
+        }
+
+        let (pubkey, signature) = input
+            .partial_sigs
+            .first()
+            .cloned()
+            .ok_or(TransactionCreateError::MissingSignatures)?;
+
+        if input.p2wpkh {
+            input.final_script_witness = Some(vec![signature, pubkey]);
+        } else {
+            let script = Script::new(Some(vec![signature, pubkey]));
+            input.final_script_sig = Some(script.as_bytes());
+        }
+    }
+
+    for (i, input) in psbt.inputs.iter().enumerate() {
+        if input.p2wpkh {
+            if let Some(witness) = &input.final_script_witness {
+                psbt.unsigned_tx.set_witness(witness.clone());
+            }
+        } else if let Some(script_sig) = &input.final_script_sig {
+            psbt.unsigned_tx.set_signature(i, script_sig.clone());
+        }
+    }
+
+    Ok(psbt.unsigned_tx)
+}
+
+fn write_compact_size(out: &mut Vec<u8>, value: u64) {
+    if value < 0xfd {
+        out.push(value as u8);
+    } else if value <= 0xffff {
+        out.push(0xfd);
+        out.extend_from_slice(&(value as u16).to_le_bytes());
+    } else if value <= 0xffff_ffff {
+        out.push(0xfe);
+        out.extend_from_slice(&(value as u32).to_le_bytes());
+    } else {
+        out.push(0xff);
+        out.extend_from_slice(&value.to_le_bytes());
+    }
+}
+
+fn read_compact_size(bytes: &[u8], pos: &mut usize) -> Result<u64, TransactionCreateError> {
+    let first = *bytes.get(*pos).ok_or(TransactionCreateError::InvalidPsbt)?;
+    *pos += 1;
+
+    match first {
+        0xfd => {
+            let slice = bytes.get(*pos..*pos + 2).ok_or(TransactionCreateError::InvalidPsbt)?;
+            *pos += 2;
+            Ok(u16::from_le_bytes(slice.try_into().map_err(|_| TransactionCreateError::InvalidPsbt)?) as u64)
+        }
+        0xfe => {
+            let slice = bytes.get(*pos..*pos + 4).ok_or(TransactionCreateError::InvalidPsbt)?;
+            *pos += 4;
+            Ok(u32::from_le_bytes(slice.try_into().map_err(|_| TransactionCreateError::InvalidPsbt)?) as u64)
+        }
+        0xff => {
+            let slice = bytes.get(*pos..*pos + 8).ok_or(TransactionCreateError::InvalidPsbt)?;
+            *pos += 8;
+            Ok(u64::from_le_bytes(slice.try_into().map_err(|_| TransactionCreateError::InvalidPsbt)?))
+        }
+        _ => Ok(first as u64),
+    }
+}
+
+fn write_kv(out: &mut Vec<u8>, key: &[u8], value: &[u8]) {
+    write_compact_size(out, key.len() as u64);
+    out.extend_from_slice(key);
+    write_compact_size(out, value.len() as u64);
+    out.extend_from_slice(value);
+}
+
+fn read_bytes<'a>(bytes: &'a [u8], pos: &mut usize, len: u64) -> Result<&'a [u8], TransactionCreateError> {
+    let len = len as usize;
+    let slice = bytes.get(*pos..*pos + len).ok_or(TransactionCreateError::InvalidPsbt)?;
+    *pos += len;
+    Ok(slice)
+}
+
+impl Psbt {
+    /// Serializes this PSBT to raw BIP174 bytes: magic, then the global,
+    /// input and output key-value maps, each terminated by a zero-length key.
+    pub fn as_bytes(&self) -> Vec<u8> {
+        let mut out = PSBT_MAGIC.to_vec();
+
+        write_kv(&mut out, &[0x00], &self.unsigned_tx.as_bytes(false));
+        out.push(0x00); // end of global map
+
+        for input in &self.inputs {
+            if let Some(witness_utxo) = &input.witness_utxo {
+                write_kv(&mut out, &[0x01], &witness_utxo.as_bytes());
+            }
+            if let Some(sighash_type) = input.sighash_type {
+                write_kv(&mut out, &[0x03], &[sighash_type]);
+            }
+            for (pubkey, signature) in &input.partial_sigs {
+                let mut key = vec![0x02];
+                key.extend_from_slice(pubkey);
+                write_kv(&mut out, &key, signature);
+            }
+            if let Some(redeem_script) = &input.redeem_script {
+                write_kv(&mut out, &[0x04], redeem_script);
+            }
+            if let Some(witness_script) = &input.witness_script {
+                write_kv(&mut out, &[0x05], witness_script);
+            }
+            if let Some(final_script_sig) = &input.final_script_sig {
+                write_kv(&mut out, &[0x07], final_script_sig);
+            }
+            if let Some(final_script_witness) = &input.final_script_witness {
+                let mut serialized = vec![];
+                write_compact_size(&mut serialized, final_script_witness.len() as u64);
+                for item in final_script_witness {
+                    write_compact_size(&mut serialized, item.len() as u64);
+                    serialized.extend_from_slice(item);
+                }
+                write_kv(&mut out, &[0x08], &serialized);
+            }
+            out.push(0x00); // end of this input's map
+        }
+
+        for output in &self.outputs {
+            if let Some(redeem_script) = &output.redeem_script {
+                write_kv(&mut out, &[0x00], redeem_script);
+            }
+            if let Some(witness_script) = &output.witness_script {
+                write_kv(&mut out, &[0x01], witness_script);
+            }
+            out.push(0x00); // end of this output's map
+        }
+
+        out
+    }
+
+    /// Base64-encodes [`Psbt::as_bytes`], the form PSBTs are usually passed
+    /// around in (QR codes, clipboard, the GUI wallet's text fields).
+    pub fn to_base64(&self) -> String {
+        base64::encode(self.as_bytes())
+    }
+
+    /// Parses raw BIP174 bytes back into a `Psbt`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Psbt, TransactionCreateError> {
+        if bytes.len() < PSBT_MAGIC.len() || bytes[..PSBT_MAGIC.len()] != PSBT_MAGIC {
+            return Err(TransactionCreateError::InvalidPsbt);
+        }
+        let mut pos = PSBT_MAGIC.len();
+
+        let mut unsigned_tx = None;
+        loop {
+            let key_len = read_compact_size(bytes, &mut pos)?;
+            if key_len == 0 {
+                break;
+            }
+            let key = read_bytes(bytes, &mut pos, key_len)?.to_vec();
+            let value_len = read_compact_size(bytes, &mut pos)?;
+            let value = read_bytes(bytes, &mut pos, value_len)?.to_vec();
+
+            if key == [0x00] {
+                unsigned_tx = Some(Transaction::from_bytes(&value).map_err(|_| TransactionCreateError::InvalidPsbt)?);
+            }
+        }
+        let unsigned_tx = unsigned_tx.ok_or(TransactionCreateError::InvalidPsbt)?;
+
+        let input_count = unsigned_tx.get_tx_in_list().len();
+        let mut inputs = vec![PsbtInput::default(); input_count];
+
+        for input in inputs.iter_mut() {
+            loop {
+                let key_len = read_compact_size(bytes, &mut pos)?;
+                if key_len == 0 {
+                    break;
+                }
+                let key = read_bytes(bytes, &mut pos, key_len)?.to_vec();
+                let value_len = read_compact_size(bytes, &mut pos)?;
+                let value = read_bytes(bytes, &mut pos, value_len)?.to_vec();
+
+                match key.first() {
+                    Some(0x01) => {
+                        input.witness_utxo = TxOut::from_bytes(&value).ok();
+                        input.p2wpkh = true;
+                    }
+                    Some(0x03) => input.sighash_type = value.first().copied(),
+                    Some(0x02) if key.len() > 1 => input.partial_sigs.push((key[1..].to_vec(), value)),
+                    Some(0x04) => input.redeem_script = Some(value),
+                    Some(0x05) => input.witness_script = Some(value),
+                    Some(0x07) => input.final_script_sig = Some(value),
+                    Some(0x08) => {
+                        let mut items = vec![];
+                        let mut item_pos = 0;
+                        let item_count = read_compact_size(&value, &mut item_pos)?;
+                        for _ in 0..item_count {
+                            let item_len = read_compact_size(&value, &mut item_pos)?;
+                            items.push(read_bytes(&value, &mut item_pos, item_len)?.to_vec());
+                        }
+                        input.final_script_witness = Some(items);
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let output_count = unsigned_tx.get_tx_out_list().len();
+        let mut outputs = vec![PsbtOutput::default(); output_count];
+        for output in outputs.iter_mut() {
+            loop {
+                let key_len = read_compact_size(bytes, &mut pos)?;
+                if key_len == 0 {
+                    break;
+                }
+                let key = read_bytes(bytes, &mut pos, key_len)?.to_vec();
+                let value_len = read_compact_size(bytes, &mut pos)?;
+                let value = read_bytes(bytes, &mut pos, value_len)?.to_vec();
+
+                match key.first() {
+                    Some(0x00) => output.redeem_script = Some(value),
+                    Some(0x01) => output.witness_script = Some(value),
+                    _ => {}
+                }
+            }
+        }
+
+        Ok(Psbt { unsigned_tx, inputs, outputs })
+    }
+
+    /// Parses a base64-encoded PSBT, the inverse of [`Psbt::to_base64`].
+    pub fn from_base64(encoded: &str) -> Result<Psbt, TransactionCreateError> {
+        let bytes = base64::decode(encoded).map_err(|_| TransactionCreateError::InvalidPsbt)?;
+        Psbt::from_bytes(&bytes)
+    }
+}
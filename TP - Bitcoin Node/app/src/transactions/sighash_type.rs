@@ -0,0 +1,61 @@
+/// Which parts of the transaction a signature commits to, per the legacy and
+/// BIP143 sighash rules.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SigHashBase {
+    /// Commits to every input and every output (the historical default).
+    All,
+    /// Commits to no outputs at all, so anyone may add outputs later.
+    None,
+    /// Commits only to the output at the same index as the signed input.
+    Single,
+}
+
+/// A full SIGHASH flag: a base type plus the optional `ANYONECANPAY`
+/// modifier, which restricts the preimage to the input being signed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SigHashType {
+    pub base: SigHashBase,
+    pub anyone_can_pay: bool,
+}
+
+impl SigHashType {
+    pub const ALL: SigHashType = SigHashType { base: SigHashBase::All, anyone_can_pay: false };
+    pub const NONE: SigHashType = SigHashType { base: SigHashBase::None, anyone_can_pay: false };
+    pub const SINGLE: SigHashType = SigHashType { base: SigHashBase::Single, anyone_can_pay: false };
+
+    pub fn with_anyone_can_pay(self) -> SigHashType {
+        SigHashType { anyone_can_pay: true, ..self }
+    }
+
+    /// The byte appended to a DER signature, as BIP143/the legacy sighash
+    /// algorithm expect it.
+    pub fn to_byte(self) -> u8 {
+        let base = match self.base {
+            SigHashBase::All => 0x01,
+            SigHashBase::None => 0x02,
+            SigHashBase::Single => 0x03,
+        };
+
+        if self.anyone_can_pay {
+            base | 0x80
+        } else {
+            base
+        }
+    }
+
+    pub fn from_byte(byte: u8) -> SigHashType {
+        let base = match byte & 0x1f {
+            0x02 => SigHashBase::None,
+            0x03 => SigHashBase::Single,
+            _ => SigHashBase::All,
+        };
+
+        SigHashType { base, anyone_can_pay: byte & 0x80 != 0 }
+    }
+}
+
+impl Default for SigHashType {
+    fn default() -> Self {
+        SigHashType::ALL
+    }
+}
@@ -0,0 +1,72 @@
+use bitcoin::block_mod::transaction::Transaction;
+use bitcoin_hashes::{sha256, Hash};
+use secp256k1::{Message, PublicKey, Secp256k1, SecretKey};
+
+use super::create_transaction_error::TransactionCreateError;
+use super::sighash_type::SigHashType;
+
+/// Everything `sign_transaction` knows about one input's signature that a
+/// backend might need: the already-computed sighash an in-memory key signs
+/// directly, and the raw pieces of the transaction a hardware wallet has to
+/// stream and hash on-device itself rather than trust from the host.
+pub struct SigningRequest<'a> {
+    /// The BIP143/legacy sighash preimage for this input, computed on the
+    /// host — what [`LocalSigner`] signs outright.
+    pub sighash: &'a [u8],
+    /// The full, unmodified transaction being signed, so a hardware wallet
+    /// can stream every input and output itself and derive (and display)
+    /// its own hash instead of trusting one computed on the host.
+    pub transaction: &'a Transaction,
+    /// This input's index within `transaction`.
+    pub input_index: usize,
+    /// The amount, in satoshis, every input in `transaction` spends —
+    /// indexed the same way as `transaction`'s own input list.
+    pub input_amounts: &'a [i64],
+    /// The scriptCode this input's signature is over.
+    pub pk_script: &'a [u8],
+    pub sighash_type: SigHashType,
+}
+
+/// Produces the DER-encoded ECDSA signature for one transaction input,
+/// keyed off its BIP32 derivation path rather than a raw private key — the
+/// abstraction `sign_transaction` needs in order to stay agnostic between an
+/// in-memory key ([`LocalSigner`]) and a hardware wallet
+/// ([`LedgerSigner`](super::ledger_signer::LedgerSigner)).
+pub trait TxSigner {
+    /// Signs the input described by `request` with the key at
+    /// `derivation_path`, returning a DER-encoded ECDSA signature with no
+    /// sighash-type byte appended.
+    fn sign_input(&mut self, request: &SigningRequest, derivation_path: &[u32]) -> Result<Vec<u8>, TransactionCreateError>;
+
+    /// The compressed public key at `derivation_path`, needed to build the
+    /// scriptSig/witness and to derive the pk_script being signed for.
+    fn public_key(&mut self, derivation_path: &[u32]) -> Result<Vec<u8>, TransactionCreateError>;
+}
+
+/// The existing in-memory backend: signs with a `SecretKey` already held in
+/// process memory, ignoring `derivation_path` since the key was already
+/// selected by whatever derived it (see `wallet::accounts::Accounts`).
+pub struct LocalSigner {
+    secret_key: SecretKey,
+}
+
+impl LocalSigner {
+    pub fn new(secret_key: SecretKey) -> LocalSigner {
+        LocalSigner { secret_key }
+    }
+}
+
+impl TxSigner for LocalSigner {
+    fn sign_input(&mut self, request: &SigningRequest, _derivation_path: &[u32]) -> Result<Vec<u8>, TransactionCreateError> {
+        let secp = Secp256k1::new();
+        let message = Message::from_hashed_data::<sha256::Hash>(request.sighash);
+
+        Ok(secp.sign_ecdsa(&message, &self.secret_key).serialize_der().to_vec())
+    }
+
+    fn public_key(&mut self, _derivation_path: &[u32]) -> Result<Vec<u8>, TransactionCreateError> {
+        let secp = Secp256k1::new();
+
+        Ok(PublicKey::from_secret_key(&secp, &self.secret_key).serialize().to_vec())
+    }
+}
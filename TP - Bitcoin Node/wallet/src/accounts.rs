@@ -0,0 +1,206 @@
+use std::collections::HashMap;
+
+use bech32::wit_prog::WitnessProgram;
+use bip39::{Language, Mnemonic};
+use bitcoin_hashes::{hash160, Hash};
+use secp256k1::{PublicKey, Secp256k1, SecretKey};
+
+use crate::accounts_error::AccountsError;
+use crate::bip32::ExtendedPrivKey;
+
+/// Number of unused addresses to keep generated ahead of the last one seen
+/// in a block/mempool transaction, per BIP44's gap limit recommendation.
+const GAP_LIMIT: u32 = 20;
+
+/// BIP32 path to the external (receive) chain of the first P2WPKH testnet account.
+const RECEIVE_PATH: &str = "m/84'/1'/0'/0";
+/// BIP32 path to the internal (change) chain of the first P2WPKH testnet account.
+const CHANGE_PATH: &str = "m/84'/1'/0'/1";
+
+/// A single derived key the wallet knows how to spend from.
+#[derive(Clone)]
+pub struct DerivedKey {
+    pub private_key: SecretKey,
+    pub address: Vec<u8>,
+    pub index: u32,
+    pub is_change: bool,
+}
+
+/// HD wallet account derived from a BIP39 mnemonic, following BIP32/BIP44.
+///
+/// `Accounts` no longer hands out a single flat private key: every address it
+/// produces is a child of the same master key, addressed by its own
+/// derivation path, so a wallet can safely spread funds across many
+/// addresses while still being restorable from the mnemonic alone.
+pub struct Accounts {
+    master: Option<ExtendedPrivKey>,
+    next_receive_index: u32,
+    next_change_index: u32,
+    /// Every derived address the wallet has generated so far, keyed by its
+    /// string representation, so `create_transaction` can resolve the
+    /// `SecretKey` that owns a given UTXO.
+    keys_by_address: HashMap<Vec<u8>, DerivedKey>,
+}
+
+impl Accounts {
+    /// Wallet with no keys, used before a mnemonic has been generated or imported.
+    pub fn new() -> Self {
+        Accounts {
+            master: None,
+            next_receive_index: 0,
+            next_change_index: 0,
+            keys_by_address: HashMap::new(),
+        }
+    }
+
+    /// Generates a fresh BIP39 mnemonic and the wallet derived from it.
+    ///
+    /// Returns the mnemonic phrase alongside the wallet so it can be shown to
+    /// the user once, for backup purposes.
+    pub fn generate(passphrase: &str) -> Result<(String, Self), AccountsError> {
+        let mnemonic = Mnemonic::generate_in(Language::English, 12)
+            .map_err(|_| AccountsError::InvalidMnemonic)?;
+        let phrase = mnemonic.to_string();
+        let accounts = Self::from_mnemonic(&phrase, passphrase)?;
+
+        Ok((phrase, accounts))
+    }
+
+    /// Restores a wallet from an existing BIP39 mnemonic phrase.
+    pub fn from_mnemonic(phrase: &str, passphrase: &str) -> Result<Self, AccountsError> {
+        let mnemonic = Mnemonic::parse_in(Language::English, phrase)
+            .map_err(|_| AccountsError::InvalidMnemonic)?;
+        let seed = mnemonic.to_seed(passphrase);
+        let master = ExtendedPrivKey::master(&seed)?;
+
+        let mut accounts = Accounts {
+            master: Some(master),
+            next_receive_index: 0,
+            next_change_index: 0,
+            keys_by_address: HashMap::new(),
+        };
+
+        accounts.fill_lookahead()?;
+
+        Ok(accounts)
+    }
+
+    fn master(&self) -> Result<&ExtendedPrivKey, AccountsError> {
+        self.master.as_ref().ok_or(AccountsError::InvalidSeed)
+    }
+
+    fn derive(&self, path: &str, index: u32) -> Result<SecretKey, AccountsError> {
+        Ok(self
+            .master()?
+            .derive_path(&format!("{}/{}", path, index))?
+            .private_key)
+    }
+
+    fn address_for(private_key: &SecretKey) -> Vec<u8> {
+        let secp = Secp256k1::new();
+        let public_key = PublicKey::from_secret_key(&secp, private_key).serialize();
+        let h160 = hash160::Hash::hash(&public_key).to_byte_array();
+
+        let witness_program = WitnessProgram {
+            version: 0,
+            program: h160.to_vec(),
+        };
+
+        witness_program
+            .to_address("tb".to_string())
+            .unwrap_or_default()
+            .into_bytes()
+    }
+
+    /// Ensures `GAP_LIMIT` unused addresses are generated ahead of the last
+    /// one returned on both the receive and change chains, so `update_wallet`
+    /// can recognise funds sent to an address the user has not yet
+    /// re-requested from the GUI.
+    fn fill_lookahead(&mut self) -> Result<(), AccountsError> {
+        let receive_target = self.next_receive_index + GAP_LIMIT;
+        while (self.keys_by_address.values().filter(|k| !k.is_change).count() as u32)
+            < receive_target
+        {
+            let index = self.keys_by_address.values().filter(|k| !k.is_change).count() as u32;
+            self.insert_derived(RECEIVE_PATH, index, false)?;
+        }
+
+        let change_target = self.next_change_index + GAP_LIMIT;
+        while (self.keys_by_address.values().filter(|k| k.is_change).count() as u32)
+            < change_target
+        {
+            let index = self.keys_by_address.values().filter(|k| k.is_change).count() as u32;
+            self.insert_derived(CHANGE_PATH, index, true)?;
+        }
+
+        Ok(())
+    }
+
+    fn insert_derived(&mut self, path: &str, index: u32, is_change: bool) -> Result<(), AccountsError> {
+        let private_key = self.derive(path, index)?;
+        let address = Self::address_for(&private_key);
+
+        self.keys_by_address.insert(
+            address.clone(),
+            DerivedKey {
+                private_key,
+                address,
+                index,
+                is_change,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Hands out the next unused receive address, advancing the external chain.
+    pub fn next_receive_address(&mut self) -> Result<Vec<u8>, AccountsError> {
+        let private_key = self.derive(RECEIVE_PATH, self.next_receive_index)?;
+        let address = Self::address_for(&private_key);
+        self.next_receive_index += 1;
+        self.fill_lookahead()?;
+
+        Ok(address)
+    }
+
+    /// Hands out the next unused change address, advancing the internal chain.
+    pub fn next_change_address(&mut self) -> Result<Vec<u8>, AccountsError> {
+        let private_key = self.derive(CHANGE_PATH, self.next_change_index)?;
+        let address = Self::address_for(&private_key);
+        self.next_change_index += 1;
+        self.fill_lookahead()?;
+
+        Ok(address)
+    }
+
+    /// Looks up the `SecretKey` that owns `address`, so a UTXO locked to it
+    /// can be spent without the caller ever handling a flat private key.
+    pub fn secret_key_for(&self, address: &[u8]) -> Option<SecretKey> {
+        self.keys_by_address
+            .get(address)
+            .map(|derived| derived.private_key)
+    }
+
+    /// Called by `update_wallet` when a transaction pays one of the
+    /// lookahead addresses, so the gap-limit window keeps sliding forward.
+    pub fn mark_address_seen(&mut self, address: &[u8]) -> Result<(), AccountsError> {
+        if let Some(derived) = self.keys_by_address.get(address) {
+            if derived.is_change {
+                self.next_change_index = self.next_change_index.max(derived.index + 1);
+            } else {
+                self.next_receive_index = self.next_receive_index.max(derived.index + 1);
+            }
+        }
+        self.fill_lookahead()
+    }
+
+    pub fn known_addresses(&self) -> impl Iterator<Item = &Vec<u8>> {
+        self.keys_by_address.keys()
+    }
+}
+
+impl Default for Accounts {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,28 @@
+use std::fmt;
+
+/// Errors raised while creating or deriving keys from an [`super::accounts::Accounts`] wallet.
+#[derive(Debug)]
+pub enum AccountsError {
+    /// The mnemonic phrase has an invalid word count or checksum.
+    InvalidMnemonic,
+    /// The BIP39 seed could not be turned into a valid BIP32 master key, or
+    /// the wallet has no master key yet (it was built with `Accounts::new`).
+    InvalidSeed,
+    /// A derivation path component was not a valid (optionally hardened) index.
+    InvalidDerivationPath,
+    /// A child key derivation produced an invalid secp256k1 scalar.
+    InvalidDerivation,
+}
+
+impl fmt::Display for AccountsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AccountsError::InvalidMnemonic => write!(f, "invalid mnemonic phrase"),
+            AccountsError::InvalidSeed => write!(f, "invalid BIP39 seed"),
+            AccountsError::InvalidDerivationPath => write!(f, "invalid BIP32 derivation path"),
+            AccountsError::InvalidDerivation => write!(f, "child key derivation failed"),
+        }
+    }
+}
+
+impl std::error::Error for AccountsError {}
@@ -0,0 +1,3 @@
+pub mod accounts;
+pub mod accounts_error;
+pub mod bip32;